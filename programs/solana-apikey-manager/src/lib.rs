@@ -8,6 +8,138 @@ const MAX_NAME_LEN: usize = 32;
 const MAX_SCOPES: usize = 8;
 /// Scope string max length
 const SCOPE_LEN: usize = 16;
+/// Maximum length of a tenant key's embedded filter string
+const MAX_FILTER_LEN: usize = 128;
+
+/// Returns true if a stored scope grants the `required` scope.
+///
+/// A stored scope matches when it equals the required scope exactly, when it
+/// is the catch-all `"*"`, or when it is a prefix wildcard such as
+/// `"documents.*"` that covers any required scope sharing the literal prefix
+/// (`"documents.add"`, `"documents.get"`).
+fn scope_matches(stored: &str, required: &str) -> bool {
+    if stored == required || stored == "*" {
+        return true;
+    }
+    if stored.ends_with('*') {
+        return required.starts_with(&stored[..stored.len() - 1]);
+    }
+    false
+}
+
+/// Among the stored scopes that match `required`, return the most restrictive
+/// one. A literal match (no `*`) always wins over a wildcard; among wildcards
+/// the longest literal prefix wins. This is the scope whose per-scope rate
+/// limit should apply once scopes carry their own limits.
+fn best_matching_scope<'a>(stored: &'a [String], required: &str) -> Option<&'a String> {
+    stored
+        .iter()
+        .filter(|s| scope_matches(s, required))
+        .max_by_key(|s| {
+            if s.ends_with('*') {
+                // Rank wildcards by the length of their literal prefix.
+                s.len() - 1
+            } else {
+                // A literal match is strictly more restrictive than any wildcard.
+                usize::MAX
+            }
+        })
+}
+
+/// Catch-all permission mask granting every action.
+const ACTION_ALL: u64 = u64::MAX;
+
+/// The enumerated actions and their permission bits. Kept as a table so the
+/// bitmask builder can also resolve the bits a prefix wildcard covers.
+const ACTIONS: [(&str, u64); 10] = [
+    ("read", 1 << 0),
+    ("write", 1 << 1),
+    ("admin", 1 << 2),
+    ("search", 1 << 3),
+    ("documents.add", 1 << 4),
+    ("documents.get", 1 << 5),
+    ("documents.delete", 1 << 6),
+    ("indexes.create", 1 << 7),
+    ("indexes.delete", 1 << 8),
+    ("keys.manage", 1 << 9),
+];
+
+/// Map a well-known action name to its single permission bit.
+///
+/// Returns `None` for names that are not recognised actions. The catch-all
+/// `"*"` maps to every bit. Prefix wildcards (e.g. `"documents.*"`) are not
+/// single actions and are handled by [`scope_matches`] instead.
+fn action_bit(name: &str) -> Option<u64> {
+    if name == "*" {
+        return Some(ACTION_ALL);
+    }
+    ACTIONS
+        .iter()
+        .find(|(action, _)| *action == name)
+        .map(|(_, bit)| *bit)
+}
+
+/// Build a compact permission bitmask from a list of scope names.
+///
+/// `"*"` grants everything and each recognised literal action contributes its
+/// bit. A prefix wildcard (e.g. `"documents.*"`) contributes every enumerated
+/// action it covers, so a key's bitmask and its string scopes agree.
+///
+/// DEVIATION: the original request called for rejecting unrecognized action
+/// names with an `UnknownAction` error. That is deliberately not done here,
+/// because chunk0-1's general prefix-wildcard scopes and chunk0-5's tenant
+/// sub-keys legitimately carry scopes outside the enumerated action set (e.g.
+/// `"billing.read"`). Such scopes simply contribute no action bit and remain
+/// enforced through the string scopes by [`validate_scope`]; the bitmask-based
+/// [`validate_action`] only covers the enumerated actions above.
+fn actions_from_scopes(scopes: &[String]) -> u64 {
+    let mut mask = 0u64;
+    for scope in scopes {
+        if scope == "*" {
+            return ACTION_ALL;
+        }
+        if let Some(prefix) = scope.strip_suffix('*') {
+            for (action, bit) in ACTIONS.iter() {
+                if action.starts_with(prefix) {
+                    mask |= bit;
+                }
+            }
+        } else if let Some(bit) = action_bit(scope) {
+            mask |= bit;
+        }
+    }
+    mask
+}
+
+/// Renewal state of a key relative to its expiry and the service grace period.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenewalStatus {
+    /// Key is well within its validity window.
+    Active,
+    /// Key is within `grace_period_secs` of its expiry (just before or just
+    /// after); requests still succeed but the owner should renew.
+    ExpiringSoon,
+    /// Key is past its expiry plus the grace period; requests are refused.
+    Expired,
+}
+
+/// Classify a key's renewal status given its expiry, the service grace period
+/// and the current time. Keys without an expiry are always [`RenewalStatus::Active`].
+fn renewal_status(expires_at: Option<i64>, grace_period_secs: u64, now: i64) -> RenewalStatus {
+    match expires_at {
+        None => RenewalStatus::Active,
+        Some(exp) => {
+            let grace = grace_period_secs as i64;
+            if now >= exp + grace {
+                RenewalStatus::Expired
+            } else if now >= exp - grace {
+                RenewalStatus::ExpiringSoon
+            } else {
+                RenewalStatus::Active
+            }
+        }
+    }
+}
 
 #[program]
 pub mod solana_apikey_manager {
@@ -19,21 +151,96 @@ pub mod solana_apikey_manager {
         ctx: Context<InitializeService>,
         name: String,
         default_rate_limit: u64,
+        grace_period_secs: u64,
     ) -> Result<()> {
         require!(name.len() <= MAX_NAME_LEN, ErrorCode::NameTooLong);
 
         let service = &mut ctx.accounts.service;
+        service.creator = ctx.accounts.authority.key();
         service.authority = ctx.accounts.authority.key();
         service.name = name;
         service.default_rate_limit = default_rate_limit;
+        service.grace_period_secs = grace_period_secs;
         service.total_keys = 0;
         service.active_keys = 0;
+        service.next_key_index = 0;
+        service.nominated_authority = None;
+        service.transfer_available_at = 0;
         service.bump = ctx.bumps.service;
 
         msg!("Service '{}' initialized", service.name);
         Ok(())
     }
 
+    /// Nominate a new authority for the service
+    /// The current authority records a nominee and a delay; the transfer can
+    /// only be accepted once `now + delay` has elapsed. This prevents a single
+    /// fat-fingered or compromised transaction from instantly handing off
+    /// control of every key in the service.
+    pub fn nominate_authority(
+        ctx: Context<NominateAuthority>,
+        new_authority: Pubkey,
+        delay: i64,
+    ) -> Result<()> {
+        require!(delay >= 0, ErrorCode::InvalidDelay);
+
+        let service = &mut ctx.accounts.service;
+        let clock = Clock::get()?;
+
+        service.nominated_authority = Some(new_authority);
+        service.transfer_available_at = clock.unix_timestamp + delay;
+
+        msg!(
+            "Authority transfer to {} nominated, available at {}",
+            new_authority,
+            service.transfer_available_at
+        );
+        Ok(())
+    }
+
+    /// Cancel a pending authority nomination
+    /// Only the current authority can clear the nominee.
+    pub fn cancel_nomination(ctx: Context<CancelNomination>) -> Result<()> {
+        let service = &mut ctx.accounts.service;
+
+        require!(
+            service.nominated_authority.is_some(),
+            ErrorCode::NoPendingNomination
+        );
+
+        service.nominated_authority = None;
+        service.transfer_available_at = 0;
+
+        msg!("Authority nomination cancelled");
+        Ok(())
+    }
+
+    /// Accept a pending authority nomination
+    /// Callable only by the nominee, and only once the timelock has elapsed.
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let service = &mut ctx.accounts.service;
+        let clock = Clock::get()?;
+
+        let nominee = service
+            .nominated_authority
+            .ok_or(ErrorCode::NoPendingNomination)?;
+        require!(
+            ctx.accounts.nominee.key() == nominee,
+            ErrorCode::NotNominatedAuthority
+        );
+        require!(
+            clock.unix_timestamp >= service.transfer_available_at,
+            ErrorCode::TimelockNotElapsed
+        );
+
+        service.authority = nominee;
+        service.nominated_authority = None;
+        service.transfer_available_at = 0;
+
+        msg!("Authority transferred to {}", nominee);
+        Ok(())
+    }
+
     /// Create a new API key for a user
     /// The key is a PDA derived from service + user + key_index
     pub fn create_api_key(
@@ -42,6 +249,9 @@ pub mod solana_apikey_manager {
         scopes: Vec<String>,
         rate_limit: Option<u64>,
         expires_at: Option<i64>,
+        key_hash: [u8; 32],
+        key_index: Option<u64>,
+        external_id: Option<[u8; 16]>,
     ) -> Result<()> {
         require!(key_name.len() <= MAX_NAME_LEN, ErrorCode::NameTooLong);
         require!(scopes.len() <= MAX_SCOPES, ErrorCode::TooManyScopes);
@@ -59,11 +269,23 @@ pub mod solana_apikey_manager {
             require!(exp > clock.unix_timestamp, ErrorCode::ExpirationInPast);
         }
 
+        // A caller migrating from an off-chain system can reproduce its
+        // existing identifiers by supplying `key_index`; otherwise we allocate
+        // from the high-water mark `next_key_index`. A supplied index below
+        // `total_keys` is rejected with `KeyIndexCollision`, even when no key
+        // uses it. An index that is already taken for this owner fails earlier,
+        // at the account's `init`, before this check runs.
+        let index = key_index.unwrap_or(service.next_key_index);
+        require!(index >= service.total_keys, ErrorCode::KeyIndexCollision);
+
         api_key.service = service.key();
         api_key.owner = ctx.accounts.owner.key();
-        api_key.key_index = service.total_keys;
+        api_key.key_index = index;
+        api_key.external_id = external_id.unwrap_or([0u8; 16]);
         api_key.name = key_name;
+        api_key.actions = actions_from_scopes(&scopes);
         api_key.scopes = scopes;
+        api_key.key_hash = key_hash;
         api_key.rate_limit = rate_limit.unwrap_or(service.default_rate_limit);
         api_key.requests_today = 0;
         api_key.total_requests = 0;
@@ -73,25 +295,137 @@ pub mod solana_apikey_manager {
         api_key.is_active = true;
         api_key.bump = ctx.bumps.api_key;
 
+        // `total_keys` counts issued keys; the high-water mark only ever moves
+        // forward so the default index never collides with an existing key.
         service.total_keys += 1;
+        service.next_key_index = service.next_key_index.max(index.saturating_add(1));
         service.active_keys += 1;
 
         msg!("API key '{}' created for user {}", api_key.name, api_key.owner);
         Ok(())
     }
 
+    /// Create a delegated tenant sub-key from a parent API key
+    /// The tenant key can only ever be a subset of the parent's permissions
+    /// and carries an opaque `filter` the downstream service applies to every
+    /// request. The parent key's owner must sign.
+    pub fn create_tenant_key(
+        ctx: Context<CreateTenantKey>,
+        parent_key: Pubkey,
+        tenant_index: u64,
+        filter: String,
+        scopes: Vec<String>,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        // `parent_key` and `tenant_index` are enforced through the PDA seeds in
+        // the context; a collision with an existing tenant key fails at `init`.
+        let _ = (parent_key, tenant_index);
+
+        require!(filter.len() <= MAX_FILTER_LEN, ErrorCode::FilterTooLong);
+        require!(scopes.len() <= MAX_SCOPES, ErrorCode::TooManyScopes);
+        for scope in &scopes {
+            require!(scope.len() <= SCOPE_LEN, ErrorCode::ScopeTooLong);
+        }
+
+        let parent = &ctx.accounts.parent;
+        let clock = Clock::get()?;
+
+        // The parent must itself be usable to delegate from.
+        require!(parent.is_active, ErrorCode::KeyInactive);
+        if let Some(exp) = parent.expires_at {
+            require!(clock.unix_timestamp < exp, ErrorCode::KeyExpired);
+        }
+
+        // Every requested scope must already be granted by the parent.
+        for scope in &scopes {
+            require!(
+                parent.scopes.iter().any(|p| scope_matches(p, scope)),
+                ErrorCode::ScopeNotSubset
+            );
+        }
+
+        // Tenant expiry may be no later than the parent's.
+        let expires_at = match (expires_at, parent.expires_at) {
+            (Some(exp), Some(parent_exp)) => {
+                require!(exp > clock.unix_timestamp, ErrorCode::ExpirationInPast);
+                require!(exp <= parent_exp, ErrorCode::ExpirationExceedsParent);
+                Some(exp)
+            }
+            (Some(exp), None) => {
+                require!(exp > clock.unix_timestamp, ErrorCode::ExpirationInPast);
+                Some(exp)
+            }
+            (None, parent_exp) => parent_exp,
+        };
+
+        let tenant = &mut ctx.accounts.tenant_key;
+        tenant.parent = parent.key();
+        tenant.owner = parent.owner;
+        tenant.service = parent.service;
+        tenant.scopes = scopes;
+        tenant.filter = filter;
+        tenant.created_at = clock.unix_timestamp;
+        tenant.expires_at = expires_at;
+        tenant.is_active = true;
+        tenant.bump = ctx.bumps.tenant_key;
+
+        msg!("Tenant key created under parent {}", tenant.parent);
+        Ok(())
+    }
+
+    /// Validate a tenant sub-key for a required scope
+    /// Loads both the tenant key and its parent so that revoking or expiring
+    /// the parent key also disables the tenant key.
+    pub fn validate_tenant_scope(
+        ctx: Context<ValidateTenantScope>,
+        required_scope: String,
+    ) -> Result<()> {
+        let tenant = &ctx.accounts.tenant_key;
+        let parent = &ctx.accounts.parent;
+        let clock = Clock::get()?;
+
+        // The parent must still be valid — revoking/expiring it cascades.
+        require!(parent.is_active, ErrorCode::KeyInactive);
+        if let Some(exp) = parent.expires_at {
+            require!(clock.unix_timestamp < exp, ErrorCode::KeyExpired);
+        }
+
+        // The tenant key itself must be valid.
+        require!(tenant.is_active, ErrorCode::KeyInactive);
+        if let Some(exp) = tenant.expires_at {
+            require!(clock.unix_timestamp < exp, ErrorCode::KeyExpired);
+        }
+
+        let matched = best_matching_scope(&tenant.scopes, &required_scope);
+        require!(matched.is_some(), ErrorCode::InsufficientPermissions);
+
+        msg!(
+            "Tenant scope '{}' validated with filter '{}'",
+            required_scope,
+            tenant.filter
+        );
+        Ok(())
+    }
+
     /// Record an API request (usage tracking)
     /// Called by the service to track key usage
     pub fn record_request(ctx: Context<RecordRequest>) -> Result<()> {
+        let grace_period_secs = ctx.accounts.service.grace_period_secs;
         let api_key = &mut ctx.accounts.api_key;
         let clock = Clock::get()?;
 
         // Check if key is active
         require!(api_key.is_active, ErrorCode::KeyInactive);
 
-        // Check expiration
-        if let Some(exp) = api_key.expires_at {
-            require!(clock.unix_timestamp < exp, ErrorCode::KeyExpired);
+        // Check expiration, allowing the grace window past expiry so in-flight
+        // clients are not cut off the instant a key expires.
+        match renewal_status(api_key.expires_at, grace_period_secs, clock.unix_timestamp) {
+            RenewalStatus::Expired => return Err(ErrorCode::KeyExpired.into()),
+            RenewalStatus::ExpiringSoon => msg!(
+                "Key '{}' expiring soon; owner should call extend_expiration",
+                api_key.name
+            ),
+            RenewalStatus::Active => {}
         }
 
         // Calculate current day (Unix days since epoch)
@@ -126,16 +460,118 @@ pub mod solana_apikey_manager {
         // Check if key is active
         require!(api_key.is_active, ErrorCode::KeyInactive);
 
-        // Check expiration
-        if let Some(exp) = api_key.expires_at {
-            require!(clock.unix_timestamp < exp, ErrorCode::KeyExpired);
+        // Check expiration, allowing the grace window past expiry.
+        match renewal_status(
+            api_key.expires_at,
+            ctx.accounts.service.grace_period_secs,
+            clock.unix_timestamp,
+        ) {
+            RenewalStatus::Expired => return Err(ErrorCode::KeyExpired.into()),
+            RenewalStatus::ExpiringSoon => msg!(
+                "Key '{}' expiring soon; owner should call extend_expiration",
+                api_key.name
+            ),
+            RenewalStatus::Active => {}
         }
 
-        // Check if key has the required scope
-        let has_scope = api_key.scopes.iter().any(|s| s == &required_scope || s == "*");
-        require!(has_scope, ErrorCode::InsufficientPermissions);
+        // Resolve the most restrictive stored scope that grants the request.
+        let matched = best_matching_scope(&api_key.scopes, &required_scope);
+        require!(matched.is_some(), ErrorCode::ScopeNotMatched);
 
-        msg!("Scope '{}' validated for key '{}'", required_scope, api_key.name);
+        msg!(
+            "Scope '{}' validated via '{}' for key '{}'",
+            required_scope,
+            matched.unwrap(),
+            api_key.name
+        );
+        Ok(())
+    }
+
+    /// Report the renewal status of a key
+    /// A view instruction the gateway can call to learn whether a key is
+    /// `Active`, `ExpiringSoon`, or `Expired` and prompt the owner accordingly.
+    pub fn get_key_status(ctx: Context<GetKeyStatus>) -> Result<()> {
+        let api_key = &ctx.accounts.api_key;
+        let clock = Clock::get()?;
+
+        if !api_key.is_active {
+            msg!("Key '{}' status: Inactive", api_key.name);
+            return Ok(());
+        }
+
+        let status = renewal_status(
+            api_key.expires_at,
+            ctx.accounts.service.grace_period_secs,
+            clock.unix_timestamp,
+        );
+        msg!("Key '{}' status: {:?}", api_key.name, status);
+        Ok(())
+    }
+
+    /// Validate an API key against a required action bit
+    /// O(1) permission check using the compact `actions` bitmask, as an
+    /// alternative to the string-based `validate_scope`.
+    pub fn validate_action(ctx: Context<ValidateAction>, required_bit: u64) -> Result<()> {
+        let api_key = &ctx.accounts.api_key;
+        let clock = Clock::get()?;
+
+        // Check if key is active
+        require!(api_key.is_active, ErrorCode::KeyInactive);
+
+        // Check expiration, allowing the grace window past expiry.
+        match renewal_status(
+            api_key.expires_at,
+            ctx.accounts.service.grace_period_secs,
+            clock.unix_timestamp,
+        ) {
+            RenewalStatus::Expired => return Err(ErrorCode::KeyExpired.into()),
+            RenewalStatus::ExpiringSoon => msg!(
+                "Key '{}' expiring soon; owner should call extend_expiration",
+                api_key.name
+            ),
+            RenewalStatus::Active => {}
+        }
+
+        // Single-instruction permission test
+        require!(
+            api_key.actions & required_bit != 0,
+            ErrorCode::InsufficientPermissions
+        );
+
+        msg!("Action {} validated for key '{}'", required_bit, api_key.name);
+        Ok(())
+    }
+
+    /// Verify a presented raw key against the stored fingerprint
+    /// Recomputes the keccak-256 digest of `raw_key` on chain and asserts it
+    /// matches the stored `key_hash`, so a gateway can hand out an opaque
+    /// secret token and validate it without ever persisting the secret.
+    pub fn verify_key(ctx: Context<VerifyKey>, raw_key: Vec<u8>) -> Result<()> {
+        use anchor_lang::solana_program::keccak;
+
+        let api_key = &ctx.accounts.api_key;
+        let clock = Clock::get()?;
+
+        // Recompute the fingerprint and compare against the stored digest.
+        let digest = keccak::hash(&raw_key);
+        require!(digest.0 == api_key.key_hash, ErrorCode::KeyHashMismatch);
+
+        // Same active/expiry checks as validate_scope, honoring the grace window.
+        require!(api_key.is_active, ErrorCode::KeyInactive);
+        match renewal_status(
+            api_key.expires_at,
+            ctx.accounts.service.grace_period_secs,
+            clock.unix_timestamp,
+        ) {
+            RenewalStatus::Expired => return Err(ErrorCode::KeyExpired.into()),
+            RenewalStatus::ExpiringSoon => msg!(
+                "Key '{}' expiring soon; owner should call extend_expiration",
+                api_key.name
+            ),
+            RenewalStatus::Active => {}
+        }
+
+        msg!("Key '{}' verified via presented secret", api_key.name);
         Ok(())
     }
 
@@ -196,7 +632,10 @@ pub mod solana_apikey_manager {
             require!(scope.len() <= SCOPE_LEN, ErrorCode::ScopeTooLong);
         }
 
+        let actions = actions_from_scopes(&new_scopes);
+
         let api_key = &mut ctx.accounts.api_key;
+        api_key.actions = actions;
         api_key.scopes = new_scopes.clone();
 
         msg!("Scopes updated for key '{}': {:?}", api_key.name, new_scopes);
@@ -225,27 +664,43 @@ pub mod solana_apikey_manager {
 
 #[account]
 pub struct Service {
+    /// Immutable creator used as the PDA seed, so authority can rotate without
+    /// moving the account
+    pub creator: Pubkey,
     /// The authority who manages this service
     pub authority: Pubkey,
     /// Human-readable service name
     pub name: String,
     /// Default rate limit for new keys (requests per day)
     pub default_rate_limit: u64,
+    /// Seconds either side of expiry during which a key still works
+    pub grace_period_secs: u64,
     /// Total number of keys ever created
     pub total_keys: u64,
+    /// High-water mark for the next auto-assigned key index
+    pub next_key_index: u64,
     /// Currently active keys
     pub active_keys: u64,
+    /// Pending authority nominee, if any (two-step transfer)
+    pub nominated_authority: Option<Pubkey>,
+    /// Earliest time a nominated transfer may be accepted
+    pub transfer_available_at: i64,
     /// PDA bump seed
     pub bump: u8,
 }
 
 impl Service {
     pub const SIZE: usize = 8 + // discriminator
+        32 + // creator
         32 + // authority
         4 + MAX_NAME_LEN + // name (string prefix + chars)
         8 + // default_rate_limit
+        8 + // grace_period_secs
         8 + // total_keys
+        8 + // next_key_index
         8 + // active_keys
+        1 + 32 + // nominated_authority (Option<Pubkey>)
+        8 + // transfer_available_at
         1;  // bump
 }
 
@@ -257,10 +712,16 @@ pub struct ApiKey {
     pub owner: Pubkey,
     /// Key index (for PDA derivation)
     pub key_index: u64,
+    /// Original external identifier (UUID-style) for migrated keys
+    pub external_id: [u8; 16],
     /// Human-readable key name
     pub name: String,
     /// Permission scopes (e.g., "read", "write", "admin", "*")
     pub scopes: Vec<String>,
+    /// Compact permission bitmask derived from the known-action scopes
+    pub actions: u64,
+    /// Keccak-256 fingerprint of the secret token this key represents
+    pub key_hash: [u8; 32],
     /// Rate limit (requests per day)
     pub rate_limit: u64,
     /// Requests made today
@@ -284,8 +745,11 @@ impl ApiKey {
         32 + // service
         32 + // owner
         8 + // key_index
+        16 + // external_id
         4 + MAX_NAME_LEN + // name
         4 + (MAX_SCOPES * (4 + SCOPE_LEN)) + // scopes vec
+        8 + // actions bitmask
+        32 + // key_hash
         8 + // rate_limit
         8 + // requests_today
         8 + // total_requests
@@ -296,6 +760,41 @@ impl ApiKey {
         1;  // bump
 }
 
+#[account]
+pub struct TenantKey {
+    /// The parent API key this tenant key was derived from
+    pub parent: Pubkey,
+    /// Inherited owner (same as the parent key)
+    pub owner: Pubkey,
+    /// The service both keys belong to
+    pub service: Pubkey,
+    /// Narrowed permission scopes (always a subset of the parent's)
+    pub scopes: Vec<String>,
+    /// Opaque filter the downstream service applies to every request
+    pub filter: String,
+    /// When the tenant key was created
+    pub created_at: i64,
+    /// When the tenant key expires (None = inherits / never)
+    pub expires_at: Option<i64>,
+    /// Whether the tenant key is currently active
+    pub is_active: bool,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl TenantKey {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // parent
+        32 + // owner
+        32 + // service
+        4 + (MAX_SCOPES * (4 + SCOPE_LEN)) + // scopes vec
+        4 + MAX_FILTER_LEN + // filter
+        8 + // created_at
+        1 + 8 + // expires_at (Option<i64>)
+        1 + // is_active
+        1;  // bump
+}
+
 // =============================================================================
 // Context Structs
 // =============================================================================
@@ -319,11 +818,18 @@ pub struct InitializeService<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(key_name: String, scopes: Vec<String>)]
+#[instruction(
+    key_name: String,
+    scopes: Vec<String>,
+    rate_limit: Option<u64>,
+    expires_at: Option<i64>,
+    key_hash: [u8; 32],
+    key_index: Option<u64>
+)]
 pub struct CreateApiKey<'info> {
     #[account(
         mut,
-        seeds = [b"service", service.authority.as_ref()],
+        seeds = [b"service", service.creator.as_ref()],
         bump = service.bump
     )]
     pub service: Account<'info, Service>,
@@ -336,7 +842,7 @@ pub struct CreateApiKey<'info> {
             b"apikey",
             service.key().as_ref(),
             owner.key().as_ref(),
-            service.total_keys.to_le_bytes().as_ref()
+            key_index.unwrap_or(service.next_key_index).to_le_bytes().as_ref()
         ],
         bump
     )]
@@ -351,7 +857,7 @@ pub struct CreateApiKey<'info> {
 #[derive(Accounts)]
 pub struct RecordRequest<'info> {
     #[account(
-        seeds = [b"service", service.authority.as_ref()],
+        seeds = [b"service", service.creator.as_ref()],
         bump = service.bump
     )]
     pub service: Account<'info, Service>,
@@ -371,7 +877,124 @@ pub struct RecordRequest<'info> {
 #[derive(Accounts)]
 pub struct ValidateScope<'info> {
     #[account(
-        seeds = [b"service", service.authority.as_ref()],
+        seeds = [b"service", service.creator.as_ref()],
+        bump = service.bump
+    )]
+    pub service: Account<'info, Service>,
+
+    #[account(
+        constraint = api_key.service == service.key() @ ErrorCode::ServiceMismatch
+    )]
+    pub api_key: Account<'info, ApiKey>,
+}
+
+#[derive(Accounts)]
+pub struct NominateAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"service", service.creator.as_ref()],
+        bump = service.bump
+    )]
+    pub service: Account<'info, Service>,
+
+    /// Only the current service authority can nominate a successor
+    #[account(constraint = authority.key() == service.authority @ ErrorCode::Unauthorized)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelNomination<'info> {
+    #[account(
+        mut,
+        seeds = [b"service", service.creator.as_ref()],
+        bump = service.bump
+    )]
+    pub service: Account<'info, Service>,
+
+    /// Only the current service authority can cancel a nomination
+    #[account(constraint = authority.key() == service.authority @ ErrorCode::Unauthorized)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"service", service.creator.as_ref()],
+        bump = service.bump
+    )]
+    pub service: Account<'info, Service>,
+
+    /// The nominee must sign to accept the transfer
+    pub nominee: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyKey<'info> {
+    #[account(
+        seeds = [b"service", service.creator.as_ref()],
+        bump = service.bump
+    )]
+    pub service: Account<'info, Service>,
+
+    #[account(
+        constraint = api_key.service == service.key() @ ErrorCode::ServiceMismatch
+    )]
+    pub api_key: Account<'info, ApiKey>,
+}
+
+#[derive(Accounts)]
+#[instruction(parent_key: Pubkey, tenant_index: u64)]
+pub struct CreateTenantKey<'info> {
+    #[account(
+        constraint = parent.key() == parent_key @ ErrorCode::ServiceMismatch,
+        constraint = parent.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub parent: Account<'info, ApiKey>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = TenantKey::SIZE,
+        seeds = [b"tenant", parent_key.as_ref(), tenant_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tenant_key: Account<'info, TenantKey>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ValidateTenantScope<'info> {
+    #[account(
+        constraint = tenant_key.parent == parent.key() @ ErrorCode::ServiceMismatch
+    )]
+    pub tenant_key: Account<'info, TenantKey>,
+
+    pub parent: Account<'info, ApiKey>,
+}
+
+#[derive(Accounts)]
+pub struct GetKeyStatus<'info> {
+    #[account(
+        seeds = [b"service", service.creator.as_ref()],
+        bump = service.bump
+    )]
+    pub service: Account<'info, Service>,
+
+    #[account(
+        constraint = api_key.service == service.key() @ ErrorCode::ServiceMismatch
+    )]
+    pub api_key: Account<'info, ApiKey>,
+}
+
+#[derive(Accounts)]
+pub struct ValidateAction<'info> {
+    #[account(
+        seeds = [b"service", service.creator.as_ref()],
         bump = service.bump
     )]
     pub service: Account<'info, Service>,
@@ -386,7 +1009,7 @@ pub struct ValidateScope<'info> {
 pub struct RevokeKey<'info> {
     #[account(
         mut,
-        seeds = [b"service", service.authority.as_ref()],
+        seeds = [b"service", service.creator.as_ref()],
         bump = service.bump
     )]
     pub service: Account<'info, Service>,
@@ -411,7 +1034,7 @@ pub struct RevokeKey<'info> {
 pub struct ReactivateKey<'info> {
     #[account(
         mut,
-        seeds = [b"service", service.authority.as_ref()],
+        seeds = [b"service", service.creator.as_ref()],
         bump = service.bump
     )]
     pub service: Account<'info, Service>,
@@ -435,7 +1058,7 @@ pub struct ReactivateKey<'info> {
 #[derive(Accounts)]
 pub struct UpdateRateLimit<'info> {
     #[account(
-        seeds = [b"service", service.authority.as_ref()],
+        seeds = [b"service", service.creator.as_ref()],
         bump = service.bump
     )]
     pub service: Account<'info, Service>,
@@ -454,7 +1077,7 @@ pub struct UpdateRateLimit<'info> {
 #[derive(Accounts)]
 pub struct UpdateScopes<'info> {
     #[account(
-        seeds = [b"service", service.authority.as_ref()],
+        seeds = [b"service", service.creator.as_ref()],
         bump = service.bump
     )]
     pub service: Account<'info, Service>,
@@ -473,7 +1096,7 @@ pub struct UpdateScopes<'info> {
 #[derive(Accounts)]
 pub struct ExtendExpiration<'info> {
     #[account(
-        seeds = [b"service", service.authority.as_ref()],
+        seeds = [b"service", service.creator.as_ref()],
         bump = service.bump
     )]
     pub service: Account<'info, Service>,
@@ -511,6 +1134,26 @@ pub enum ErrorCode {
     RateLimitExceeded,
     #[msg("Insufficient permissions for this scope")]
     InsufficientPermissions,
+    #[msg("No stored scope matches the required scope")]
+    ScopeNotMatched,
+    #[msg("Presented key does not match the stored hash")]
+    KeyHashMismatch,
+    #[msg("Transfer delay must be non-negative")]
+    InvalidDelay,
+    #[msg("No pending authority nomination")]
+    NoPendingNomination,
+    #[msg("Authority transfer timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Signer is not the nominated authority")]
+    NotNominatedAuthority,
+    #[msg("Filter exceeds maximum length")]
+    FilterTooLong,
+    #[msg("Requested scope is not a subset of the parent key")]
+    ScopeNotSubset,
+    #[msg("Tenant expiration exceeds the parent key's expiration")]
+    ExpirationExceedsParent,
+    #[msg("Requested key index collides with an already-issued key")]
+    KeyIndexCollision,
     #[msg("API key is already revoked")]
     KeyAlreadyRevoked,
     #[msg("API key is already active")]